@@ -0,0 +1,130 @@
+use rand::Rng;
+use crate::PlotData;
+
+const GRID_POINTS: usize = 1440;
+
+/// A typical clear-sky PV curve computed across many days, together with a
+/// bootstrap confidence band around it.
+///
+/// All three vectors share the same `grid` of x-values in `0..=1`, one per
+/// minute of a normalized day.
+pub(crate) struct TypicalCurve {
+    pub(crate) grid: Vec<f64>,
+    pub(crate) median: Vec<f64>,
+    pub(crate) lower: Vec<f64>,
+    pub(crate) upper: Vec<f64>,
+}
+
+/// Combines a set of already-normalized daily curves (x, pv both in `0..=1`) into a
+/// single typical clear-sky curve with a bootstrap confidence band.
+///
+/// Days with no positive PV are skipped before resampling. Returns `None` if fewer
+/// than two days remain, since a confidence band is meaningless with less than that.
+///
+/// # Arguments
+///
+/// * 'daily_curves' - one normalized day's worth of PlotData per day
+/// * 'bootstrap_iterations' - number of bootstrap resamples to compute the band from
+pub(crate) fn typical_curve(daily_curves: &[Vec<PlotData>], bootstrap_iterations: usize) -> Option<TypicalCurve> {
+    let grid: Vec<f64> = (0..GRID_POINTS).map(|i| i as f64 / (GRID_POINTS - 1) as f64).collect();
+
+    let resampled: Vec<Vec<f64>> = daily_curves.iter()
+        .filter(|day| day.iter().any(|p| p.pv > 0.0))
+        .map(|day| resample(day, &grid))
+        .collect();
+
+    if resampled.len() < 2 {
+        return None;
+    }
+
+    let median = grid.iter().enumerate()
+        .map(|(i, _)| median_at(&resampled, i))
+        .collect();
+
+    let (lower, upper) = bootstrap_bands(&resampled, bootstrap_iterations);
+
+    Some(TypicalCurve { grid, median, lower, upper })
+}
+
+/// Resamples a normalized day's curve onto `grid` by linear interpolation, reusing
+/// the same piecewise-linear approach as [`crate::interpolate`].
+///
+/// # Arguments
+///
+/// * 'day' - the normalized day to resample, sorted by ascending x
+/// * 'grid' - the shared x-values to resample onto
+fn resample(day: &[PlotData], grid: &[f64]) -> Vec<f64> {
+    grid.iter().map(|&x| interpolate_at(day, x)).collect()
+}
+
+/// Linearly interpolates the PV value of `day` at a given x, clamping to the
+/// nearest endpoint outside the day's own range.
+fn interpolate_at(day: &[PlotData], x: f64) -> f64 {
+    if x <= day[0].x {
+        return day[0].pv;
+    }
+    if x >= day[day.len() - 1].x {
+        return day[day.len() - 1].pv;
+    }
+
+    let i = day.partition_point(|p| p.x < x).max(1);
+    let (p1, p2) = (&day[i - 1], &day[i]);
+    let k = (p2.pv - p1.pv) / (p2.x - p1.x);
+    p1.pv + k * (x - p1.x)
+}
+
+/// Point-wise median across all days at grid index `i`.
+fn median_at(resampled: &[Vec<f64>], i: usize) -> f64 {
+    let mut values: Vec<f64> = resampled.iter().map(|day| day[i]).collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Performs bootstrap resampling over the days to produce a 2.5th/97.5th percentile
+/// confidence band at each grid point.
+///
+/// Each iteration draws `resampled.len()` day-indices with replacement and computes
+/// the point-wise mean curve over that draw; the bands are the percentiles of those
+/// per-iteration means.
+fn bootstrap_bands(resampled: &[Vec<f64>], iterations: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = resampled.len();
+    let mut rng = rand::thread_rng();
+    let mut iteration_means: Vec<Vec<f64>> = Vec::with_capacity(iterations);
+
+    for _ in 0..iterations {
+        let draw: Vec<usize> = (0..n).map(|_| rng.gen_range(0..n)).collect();
+        let mean: Vec<f64> = (0..GRID_POINTS)
+            .map(|i| draw.iter().map(|&d| resampled[d][i]).sum::<f64>() / n as f64)
+            .collect();
+        iteration_means.push(mean);
+    }
+
+    let mut lower = vec![0.0; GRID_POINTS];
+    let mut upper = vec![0.0; GRID_POINTS];
+    for i in 0..GRID_POINTS {
+        let mut column: Vec<f64> = iteration_means.iter().map(|m| m[i]).collect();
+        column.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        lower[i] = percentile(&column, 2.5);
+        upper[i] = percentile(&column, 97.5);
+    }
+
+    (lower, upper)
+}
+
+/// Returns the given percentile (0..100) of an already-sorted slice.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0) * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    if lo == hi {
+        sorted[lo]
+    } else {
+        let frac = rank - lo as f64;
+        sorted[lo] * (1.0 - frac) + sorted[hi] * frac
+    }
+}
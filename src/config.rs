@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+
+/// Configuration describing a batch of days to normalize into PV reference curves.
+///
+/// Loaded from a TOML file given on the command line. `input_dir` and `output_dir`
+/// are joined with each [`Day`]'s `csv` to find the source file and to place the
+/// resulting JSON/PNG pair.
+#[derive(Deserialize)]
+pub struct Config {
+    pub input_dir: String,
+    pub output_dir: String,
+    pub width: u32,
+    pub height: u32,
+    pub smooth_radius: u32,
+    pub smooth_kernel: Kernel,
+    pub scale_factor: f64,
+    pub y_max: f64,
+    pub days: Vec<Day>,
+}
+
+/// The weighting scheme used by [`crate::smooth`] across its window.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Kernel {
+    /// Every point in the window is weighted equally.
+    Box,
+    /// Points are weighted by a Gaussian centered on the window, `sigma ≈ radius / 2`.
+    Gaussian,
+}
+
+/// A single day to normalize, as listed in the `[[days]]` array of the config file.
+#[derive(Deserialize)]
+pub struct Day {
+    pub csv: String,
+    pub title: Option<String>,
+    pub cutoff: Option<f64>,
+    pub disable: Option<bool>,
+}
+
+/// Errors that can occur while loading a [`Config`] from disk.
+pub struct ConfigError(pub String);
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {ConfigError(e.to_string())}
+}
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {ConfigError(e.to_string())}
+}
+
+/// Reads and parses the TOML config file at `path`.
+///
+/// # Arguments
+///
+/// * 'path' - the path to the config.toml file to load
+pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
+    let content = fs::read_to_string(path)?;
+    let config: Config = toml::from_str(&content)?;
+    Ok(config)
+}
+
+impl Day {
+    /// Whether this day entry is enabled, i.e. not explicitly disabled.
+    pub fn is_enabled(&self) -> bool {
+        !self.disable.unwrap_or(false)
+    }
+
+    /// The low-PV noise cutoff to apply for this day, defaulting to 0.0.
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff.unwrap_or(0.0)
+    }
+}
@@ -1,12 +1,20 @@
+mod average;
+mod config;
+mod plot;
+mod split;
+
 use std::fs;
 use std::fs::File;
 use std::num::ParseFloatError;
 use std::path::Path;
 use chrono::{DateTime, Local, NaiveDateTime, ParseError, Timelike};
-use plotters::prelude::*;
+use clap::{Parser, Subcommand};
+use plotters::style::{BLUE, RED};
 use serde::Serialize;
+use config::{load_config, Day, Kernel};
+use plot::{select_plotter, PlotSeries};
 
-struct CSVError(String);
+pub(crate) struct CSVError(pub(crate) String);
 impl From<std::io::Error> for CSVError {
     fn from(e: std::io::Error) -> Self {CSVError(e.to_string())}
 }
@@ -16,16 +24,19 @@ impl From<ParseError> for CSVError {
 impl From<ParseFloatError> for CSVError {
     fn from(e: ParseFloatError) -> Self {CSVError(e.to_string())}
 }
+impl From<csv::Error> for CSVError {
+    fn from(e: csv::Error) -> Self {CSVError(e.to_string())}
+}
 struct PowerRecord {
     date_time: DateTime<Local>,
     pv_power: f64,
 }
 
 #[derive(Clone)]
-struct PlotData {
-    minutes: u32,
-    x: f64,
-    pv: f64,
+pub(crate) struct PlotData {
+    pub(crate) minutes: u32,
+    pub(crate) x: f64,
+    pub(crate) pv: f64,
 }
 
 #[derive(Serialize)]
@@ -36,40 +47,224 @@ struct Data {
 #[derive(Serialize)]
 struct PVDiagram {
     pv_data: Vec<Data>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lower: Option<Vec<Data>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upper: Option<Vec<Data>>,
+}
+
+/// Command line arguments for the mygrid_pv tool.
+#[derive(Parser)]
+#[command(author, version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Normalize a batch of days from a config.toml into per-day JSON + PNG outputs
+    Normalize {
+        /// Path to the batch config.toml file to load
+        #[arg(long, default_value = "config.toml")]
+        config: String,
+    },
+    /// Compute a typical clear-sky curve across all enabled days, with bootstrap
+    /// confidence bands, and write it to a single JSON + PNG pair
+    Average {
+        /// Path to the batch config.toml file to load
+        #[arg(long, default_value = "config.toml")]
+        config: String,
+
+        /// Path to write the averaged PV diagram JSON to
+        #[arg(long, default_value = "typical.json")]
+        diagram_json: String,
+
+        /// Path to write the rendered plot of the averaged curve to
+        #[arg(long, default_value = "typical.png")]
+        plot_png: String,
+
+        /// Number of bootstrap resamples used to compute the confidence band
+        #[arg(long, default_value_t = 1000)]
+        bootstrap_iterations: usize,
+    },
+    /// Slice a multi-day master stats CSV into a per-day CSV by timestamp range
+    Split {
+        /// Path to the master stats CSV to read
+        #[arg(long)]
+        input: String,
+
+        /// Path to write the sliced CSV to
+        #[arg(long)]
+        output: String,
+
+        /// Start of the date_time window, inclusive, as an RFC3339 timestamp
+        #[arg(long)]
+        start: String,
+
+        /// End of the date_time window, inclusive, as an RFC3339 timestamp
+        #[arg(long)]
+        end: String,
+    },
 }
 
-/// Program that takes a mygrid stats file as input and produces a normalized file over
-/// the PV production of a sunny day. It also produces a plot file.
+/// Entry point. Dispatches to the per-day normalize pipeline, the cross-day
+/// averaging pipeline or the CSV split, depending on the subcommand given.
 fn main() {
-    let stats_file = "C:/Develop/mygrid_pv/20250403.csv";
-    let pv_diagram_file = "C:/Slask/mygrid_dev/config/pv_diagram.json";
-    let pv_plot_file = "C:/Slask/mygrid/pv_diagram.png";
-
-    match get_csv_record(Path::new(stats_file)) {
-        Ok((records, _)) => {
-            let mut plot_data: Vec<PlotData> = Vec::new();
-            for record in records {
-                let data_point = PlotData {
-                    minutes: record.date_time.hour() * 60 + record.date_time.minute(),
-                    x: 0.0,
-                    pv: record.pv_power * 10.0,
-                };
-
-                plot_data.push(data_point);
-            }
-            let mut plt = smooth(plot_data);
-            plt = smooth(plt);
-            plt = stretch(plt);
-            plt = interpolate(plt);
-            plt = normalize(plt);
-            save_pv_diagram(pv_diagram_file, &plt);
-            plot_diagram(pv_plot_file, plt);
+    let cli = Cli::parse();
 
+    match cli.command {
+        Commands::Normalize { config } => run_normalize(&config),
+        Commands::Average { config, diagram_json, plot_png, bootstrap_iterations } => {
+            run_average(&config, &diagram_json, &plot_png, bootstrap_iterations)
         }
-        Err(e) => {eprintln!("{:?}", e.0)}
+        Commands::Split { input, output, start, end } => run_split(&input, &output, &start, &end),
     }
 }
 
+/// Parses the `--start`/`--end` RFC3339 timestamps and streams the matching rows of
+/// `input` into `output` via [`split::split`].
+///
+/// # Arguments
+///
+/// * 'input' - path to the master stats CSV to read
+/// * 'output' - path to write the sliced CSV to
+/// * 'start' - RFC3339 lower bound (inclusive) of the `date_time` window
+/// * 'end' - RFC3339 upper bound (inclusive) of the `date_time` window
+fn run_split(input: &str, output: &str, start: &str, end: &str) {
+    let parse = |s: &str| DateTime::parse_from_rfc3339(s).map(|dt| dt.with_timezone(&Local));
+
+    let (start, end) = match (parse(start), parse(end)) {
+        (Ok(start), Ok(end)) => (start, end),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    if let Err(e) = split::split(Path::new(input), Path::new(output), start, end) {
+        eprintln!("{}", e.0);
+    }
+}
+
+/// Runs the per-day normalization pipeline: for each enabled day in the config,
+/// writes a normalized PV diagram JSON and a matching plot file.
+///
+/// # Arguments
+///
+/// * 'config_path' - path to the batch config.toml file to load
+fn run_normalize(config_path: &str) {
+    let config = match load_config(Path::new(config_path)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{:?}", e.0);
+            return;
+        }
+    };
+
+    for day in config.days.iter().filter(|d| d.is_enabled()) {
+        if let Err(e) = process_day(&config, day) {
+            eprintln!("{}: {}", day.csv, e.0);
+        }
+    }
+}
+
+/// Runs the cross-day averaging pipeline: normalizes every enabled day, combines
+/// them into a single typical clear-sky curve with a bootstrap confidence band,
+/// and writes the result to a JSON + PNG pair.
+///
+/// # Arguments
+///
+/// * 'config_path' - path to the batch config.toml file to load
+/// * 'diagram_json' - path to write the averaged PV diagram JSON to
+/// * 'plot_png' - path to write the rendered plot to
+/// * 'bootstrap_iterations' - number of bootstrap resamples to compute the band from
+fn run_average(config_path: &str, diagram_json: &str, plot_png: &str, bootstrap_iterations: usize) {
+    if bootstrap_iterations == 0 {
+        eprintln!("bootstrap-iterations must be at least 1");
+        return;
+    }
+
+    let config = match load_config(Path::new(config_path)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{:?}", e.0);
+            return;
+        }
+    };
+
+    let mut daily_curves: Vec<Vec<PlotData>> = Vec::new();
+    for day in config.days.iter().filter(|d| d.is_enabled()) {
+        match normalized_day(&config, day) {
+            Ok(plt) => daily_curves.push(plt),
+            Err(e) => eprintln!("{}: {}", day.csv, e.0),
+        }
+    }
+
+    let Some(typical) = average::typical_curve(&daily_curves, bootstrap_iterations) else {
+        eprintln!("no usable days to average");
+        return;
+    };
+
+    save_typical_diagram(diagram_json, &typical);
+    plot_typical_diagram(plot_png, &typical, (config.width, config.height), config.y_max);
+}
+
+/// Runs the normalization pipeline up to (and including) `normalize`, without
+/// writing any output, so the resulting curve can be fed into [`average::typical_curve`].
+///
+/// # Arguments
+///
+/// * 'config' - the batch config the day belongs to
+/// * 'day' - the day entry to process
+fn normalized_day(config: &config::Config, day: &Day) -> Result<Vec<PlotData>, CSVError> {
+    let stats_file = Path::new(&config.input_dir).join(&day.csv);
+    let cutoff = day.cutoff();
+
+    let (records, _) = get_csv_record(&stats_file)?;
+
+    let mut plot_data: Vec<PlotData> = Vec::new();
+    for record in records {
+        let pv = record.pv_power * config.scale_factor;
+        plot_data.push(PlotData {
+            minutes: record.date_time.hour() * 60 + record.date_time.minute(),
+            x: 0.0,
+            pv: if pv > cutoff { pv } else { 0.0 },
+        });
+    }
+
+    let mut plt = smooth(plot_data, config.smooth_radius as usize, config.smooth_kernel);
+    plt = stretch(plt)?;
+    plt = interpolate(plt);
+    plt = normalize(plt);
+
+    Ok(plt)
+}
+
+/// Runs the normalization pipeline for a single day and writes its JSON + PNG outputs.
+///
+/// # Arguments
+///
+/// * 'config' - the batch config the day belongs to
+/// * 'day' - the day entry to process
+fn process_day(config: &config::Config, day: &Day) -> Result<(), CSVError> {
+    let stem = Path::new(&day.csv).file_stem().and_then(|s| s.to_str()).unwrap_or("day");
+    let diagram_file = Path::new(&config.output_dir).join(format!("{stem}.json"));
+    let plot_file = Path::new(&config.output_dir).join(format!("{stem}.png"));
+
+    let plt = normalized_day(config, day)?;
+    save_pv_diagram(diagram_file.to_str().unwrap(), &plt);
+    plot_diagram(
+        plot_file.to_str().unwrap(),
+        plt,
+        (config.width, config.height),
+        config.y_max,
+        day.title.as_deref().unwrap_or("PVPower"),
+    );
+
+    Ok(())
+}
+
 /// Normalizes a vector of PlotData to X 0..1 and Y 0..1
 ///
 /// # Arguments
@@ -97,7 +292,7 @@ fn normalize(input: Vec<PlotData>) -> Vec<PlotData> {
 /// # Arguments
 ///
 /// * 'input' - vector to stretch
-fn stretch(input: Vec<PlotData>) -> Vec<PlotData> {
+fn stretch(input: Vec<PlotData>) -> Result<Vec<PlotData>, CSVError> {
     let mut result: Vec<PlotData> = Vec::new();
 
     for i in input {
@@ -105,6 +300,10 @@ fn stretch(input: Vec<PlotData>) -> Vec<PlotData> {
             result.push(i);
         }
     }
+    if result.is_empty() {
+        return Err(CSVError("no positive PV production found".to_string()));
+    }
+
     let start = result[0].minutes as f64;
     let end = result[result.len()-1].minutes as f64;
     let factor = 1439.0 / (end - start);
@@ -115,7 +314,7 @@ fn stretch(input: Vec<PlotData>) -> Vec<PlotData> {
         p.minutes = minutes.round().max(0.0).min(1439.0) as u32
     });
 
-    result
+    Ok(result)
 }
 
 /// Interpolate the gaps in the given vector of PlotData
@@ -149,24 +348,52 @@ fn interpolate(input: Vec<PlotData>) -> Vec<PlotData> {
     result
 }
 
-/// Performs one round of simple box smoothing of the input vector of PlotData
+/// Smooths the input vector of PlotData with a weighted window of the given radius,
+/// using either a box (flat) or Gaussian kernel.
+///
+/// Near the edges the window is shrunk symmetrically, so every window stays centered
+/// on its own sample and the curve isn't skewed at its boundaries. The first and last
+/// samples get a window of radius 0 and so keep their raw values.
 ///
 /// # Arguments
 ///
 /// * 'input' - vector to smooth
-fn smooth(input: Vec<PlotData>) -> Vec<PlotData> {
-    let mut result: Vec<PlotData> = Vec::new();
-    result.push(input[0].clone());
-    for i in 1..input.len() - 1 {
-        result.push(PlotData{
-            minutes: input[i].minutes,
-            x: input[i].x,
-            pv: (input[i-1].pv + input[i].pv + input[i+1].pv) / 3.0,
-        });
-    }
+/// * 'radius' - half-width of the smoothing window, in samples
+/// * 'kernel' - the weighting scheme to apply across the window
+fn smooth(input: Vec<PlotData>, radius: usize, kernel: Kernel) -> Vec<PlotData> {
+    let n = input.len();
 
-    result.push(input[input.len() - 1].clone());
-    result
+    (0..n).map(|i| {
+        let r = radius.min(i).min(n - 1 - i);
+        let weights = kernel_weights(kernel, r);
+
+        let pv = weights.iter().enumerate()
+            .map(|(j, w)| input[i + j - r].pv * w)
+            .sum();
+
+        PlotData { minutes: input[i].minutes, x: input[i].x, pv }
+    }).collect()
+}
+
+/// Computes the normalized weights for a smoothing window of radius `r`.
+///
+/// # Arguments
+///
+/// * 'kernel' - the weighting scheme to apply across the window
+/// * 'r' - half-width of the window, in samples
+fn kernel_weights(kernel: Kernel, r: usize) -> Vec<f64> {
+    match kernel {
+        Kernel::Box => vec![1.0 / (2 * r + 1) as f64; 2 * r + 1],
+        Kernel::Gaussian => {
+            let sigma = (r as f64 / 2.0).max(f64::EPSILON);
+            let raw: Vec<f64> = (0..=2 * r).map(|j| {
+                let d = j as f64 - r as f64;
+                (-(d * d) / (2.0 * sigma * sigma)).exp()
+            }).collect();
+            let sum: f64 = raw.iter().sum();
+            raw.iter().map(|w| w / sum).collect()
+        }
+    }
 }
 
 /// Plots a diagram based on data from PlotData struct
@@ -175,38 +402,33 @@ fn smooth(input: Vec<PlotData>) -> Vec<PlotData> {
 ///
 /// * 'plot_file' - the file to save the plot diagram in
 /// * 'plot_data' - the vector of PlotData to plot
-fn plot_diagram(plot_file: &str, plot_data: Vec<PlotData>) {
-    let root = BitMapBackend::new(plot_file, (1280, 480)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let mut chart = ChartBuilder::on(&root)
-        .caption("PVPower", ("sans-serif", 50).into_font())
-        .margin(5)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0f64..1.1f64, 0f64..1.5f64).unwrap();
-
-    chart.configure_mesh().draw().unwrap();
-
-    chart
-        .draw_series(LineSeries::new(
-            plot_data.iter().map(|dp| (dp.x, dp.pv)),
-            &RED,
-        )).unwrap()
-        .label("pvPower")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
-
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw().unwrap();
-
-    root.present().unwrap();
+/// * 'dimensions' - the (width, height) of the plot in pixels
+/// * 'y_max' - the Y-axis ceiling to use for the plot
+/// * 'title' - the caption to draw above the plot
+fn plot_diagram(plot_file: &str, plot_data: Vec<PlotData>, dimensions: (u32, u32), y_max: f64, title: &str) {
+    let series = PlotSeries {
+        label: "pvPower",
+        color: RED,
+        points: plot_data.iter().map(|dp| (dp.x, dp.pv)).collect(),
+    };
+
+    select_plotter(plot_file).render(plot_file, dimensions, y_max, title, &[series]);
+}
 
 
+/// Parses a mygrid stats `date_time` column, as found in both the master stats CSV
+/// and the per-day CSVs sliced from it, into a local timestamp.
+///
+/// # Arguments
+///
+/// * 's' - the date_time string to parse, formatted as `%Y-%m-%d %H:%M`
+pub(crate) fn parse_stats_datetime(s: &str) -> Result<DateTime<Local>, CSVError> {
+    let date_time = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")?
+        .and_local_timezone(Local)
+        .unwrap();
+    Ok(date_time)
 }
 
-
 /// Opens and read CSV file into a vector of PowerRecord
 ///
 /// # Arguments
@@ -222,9 +444,7 @@ fn get_csv_record(path: &Path) -> Result<(Vec<PowerRecord>, DateTime<Local>), CS
         let string_record = record.map_err(|e| CSVError(e.to_string()))?;
 
         let dt = string_record.get(0).ok_or(CSVError("Empty date_time".to_string()))?;
-        let date_time = NaiveDateTime::parse_from_str(dt, "%Y-%m-%d %H:%M")?
-            .and_local_timezone(Local)
-            .unwrap();
+        let date_time = parse_stats_datetime(dt)?;
         let pv_power = string_record.get(1)
             .ok_or(CSVError("Empty pv_power".to_string()))?
             .parse::<f64>()?;
@@ -256,10 +476,50 @@ fn save_pv_diagram(config_file: &str, input: &Vec<PlotData>) {
     for i in input {
         pv_data.push(Data{ x: i.x, y: i.pv })
     }
-    let pv_diagram = PVDiagram { pv_data };
+    let pv_diagram = PVDiagram { pv_data, lower: None, upper: None };
 
     let json = serde_json::to_string(&pv_diagram).unwrap();
 
     let path = Path::new(config_file);
     fs::write(path, json).unwrap();
 }
+
+/// Saves a typical clear-sky curve, with its bootstrap confidence band, to a json file
+///
+/// # Arguments
+///
+/// * 'diagram_file' - the file to save the PVDiagram struct into
+/// * 'typical' - the typical curve to save as json
+fn save_typical_diagram(diagram_file: &str, typical: &average::TypicalCurve) {
+    let pv_data = typical.grid.iter().zip(&typical.median)
+        .map(|(&x, &y)| Data { x, y }).collect();
+    let lower = typical.grid.iter().zip(&typical.lower)
+        .map(|(&x, &y)| Data { x, y }).collect();
+    let upper = typical.grid.iter().zip(&typical.upper)
+        .map(|(&x, &y)| Data { x, y }).collect();
+    let pv_diagram = PVDiagram { pv_data, lower: Some(lower), upper: Some(upper) };
+
+    let json = serde_json::to_string(&pv_diagram).unwrap();
+
+    let path = Path::new(diagram_file);
+    fs::write(path, json).unwrap();
+}
+
+/// Plots a typical clear-sky curve together with its lower/upper bootstrap bands
+///
+/// # Arguments
+///
+/// * 'plot_file' - the file to save the plot diagram in
+/// * 'typical' - the typical curve to plot
+/// * 'dimensions' - the (width, height) of the plot in pixels
+/// * 'y_max' - the Y-axis ceiling to use for the plot
+fn plot_typical_diagram(plot_file: &str, typical: &average::TypicalCurve, dimensions: (u32, u32), y_max: f64) {
+    let zip = |values: &[f64]| typical.grid.iter().zip(values).map(|(&x, &y)| (x, y)).collect();
+    let series = [
+        PlotSeries { label: "lower", color: BLUE, points: zip(&typical.lower) },
+        PlotSeries { label: "upper", color: BLUE, points: zip(&typical.upper) },
+        PlotSeries { label: "median", color: RED, points: zip(&typical.median) },
+    ];
+
+    select_plotter(plot_file).render(plot_file, dimensions, y_max, "Typical clear-sky PVPower", &series);
+}
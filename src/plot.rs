@@ -0,0 +1,83 @@
+use std::path::Path;
+use plotters::prelude::*;
+
+/// One labelled line series to draw, e.g. a day's normalized PV curve or one of
+/// the lower/median/upper bands of a typical curve.
+pub(crate) struct PlotSeries<'a> {
+    pub(crate) label: &'a str,
+    pub(crate) color: RGBColor,
+    pub(crate) points: Vec<(f64, f64)>,
+}
+
+/// Renders a set of line series to an output file.
+///
+/// Implementations pick a concrete `plotters` backend (raster or vector); selection
+/// between them happens in [`select_plotter`] based on the output file's extension.
+pub(crate) trait Plotter {
+    fn render(&self, plot_file: &str, dimensions: (u32, u32), y_max: f64, title: &str, series: &[PlotSeries]);
+}
+
+/// Renders to a raster PNG via `plotters`' `BitMapBackend`.
+pub(crate) struct PngPlotter;
+
+/// Renders to a scalable SVG via `plotters`' `SVGBackend`.
+pub(crate) struct SvgPlotter;
+
+impl Plotter for PngPlotter {
+    fn render(&self, plot_file: &str, dimensions: (u32, u32), y_max: f64, title: &str, series: &[PlotSeries]) {
+        let root = BitMapBackend::new(plot_file, dimensions).into_drawing_area();
+        draw(&root, y_max, title, series);
+    }
+}
+
+impl Plotter for SvgPlotter {
+    fn render(&self, plot_file: &str, dimensions: (u32, u32), y_max: f64, title: &str, series: &[PlotSeries]) {
+        let root = SVGBackend::new(plot_file, dimensions).into_drawing_area();
+        draw(&root, y_max, title, series);
+    }
+}
+
+/// Picks the plotter matching the output file's extension: `.svg` renders vector
+/// output, anything else (the default) renders a raster PNG.
+///
+/// # Arguments
+///
+/// * 'plot_file' - the output path whose extension decides the backend
+pub(crate) fn select_plotter(plot_file: &str) -> Box<dyn Plotter> {
+    match Path::new(plot_file).extension().and_then(|e| e.to_str()) {
+        Some("svg") => Box::new(SvgPlotter),
+        _ => Box::new(PngPlotter),
+    }
+}
+
+/// Shared rendering logic for both backends: draws a mesh, every series as a line,
+/// and a legend.
+fn draw<DB: DrawingBackend>(root: &DrawingArea<DB, plotters::coord::Shift>, y_max: f64, title: &str, series: &[PlotSeries])
+where
+    DB::ErrorType: std::fmt::Debug,
+{
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, ("sans-serif", 50).into_font())
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0f64..1.1f64, 0f64..y_max).unwrap();
+
+    chart.configure_mesh().draw().unwrap();
+
+    for s in series {
+        chart
+            .draw_series(LineSeries::new(s.points.iter().cloned(), &s.color)).unwrap()
+            .label(s.label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &s.color));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(&WHITE.mix(0.8))
+        .border_style(&BLACK)
+        .draw().unwrap();
+
+    root.present().unwrap();
+}
@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::path::Path;
+use chrono::{DateTime, Local};
+use crate::{parse_stats_datetime, CSVError};
+
+const PROGRESS_INTERVAL: u64 = 200_000;
+
+/// Slices a large, time-sorted `stats.csv` into a smaller CSV containing only the
+/// rows whose `date_time` falls within `[start, end]`.
+///
+/// Streams the input row by row rather than loading it into memory, since a
+/// season-long master CSV can be far larger than what the normalization pipeline
+/// expects as a single day's input.
+///
+/// # Arguments
+///
+/// * 'input' - path to the master stats CSV to read
+/// * 'output' - path to write the matching rows to
+/// * 'start' - lower bound (inclusive) of the `date_time` window
+/// * 'end' - upper bound (inclusive) of the `date_time` window
+pub(crate) fn split(input: &Path, output: &Path, start: DateTime<Local>, end: DateTime<Local>) -> Result<(), CSVError> {
+    let mut rdr = csv::Reader::from_reader(File::open(input)?);
+    let mut wtr = csv::Writer::from_writer(File::create(output)?);
+
+    wtr.write_record(rdr.headers()?)?;
+
+    let mut rows_read: u64 = 0;
+    let mut rows_written: u64 = 0;
+
+    for record in rdr.records() {
+        let record = record.map_err(|e| CSVError(e.to_string()))?;
+        rows_read += 1;
+
+        let dt = record.get(0).ok_or(CSVError("Empty date_time".to_string()))?;
+        let date_time = parse_stats_datetime(dt)?;
+
+        if date_time >= start && date_time <= end {
+            wtr.write_record(&record)?;
+            rows_written += 1;
+        }
+
+        if rows_read % PROGRESS_INTERVAL == 0 {
+            eprintln!("split: scanned {rows_read} rows, kept {rows_written}");
+        }
+    }
+
+    wtr.flush()?;
+    eprintln!("split: done, scanned {rows_read} rows, kept {rows_written}");
+
+    Ok(())
+}